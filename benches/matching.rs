@@ -7,6 +7,7 @@ use test::Bencher;
 use std::path::PathBuf;
 use bloodhound::entry;
 use bloodhound::matching::find;
+use bloodhound::matching::MatchOptions;
 
 #[bench]
 fn bench_find(b: &mut Bencher) {
@@ -15,11 +16,26 @@ fn bench_find(b: &mut Bencher) {
         entry::new("lib/hounds.rs".to_string()),
         entry::new("Houndfile".to_string())
     ];
-    b.iter(|| find("match", &haystack, 5));
+    b.iter(|| find("match", &haystack, 5, &MatchOptions::default()));
+}
+
+#[bench]
+fn bench_find_with_prefilter(b: &mut Bencher) {
+    // A haystack where some entries lack at least one of the query's
+    // characters, so the character-bag prefilter skips them before the
+    // fragment scorer runs.
+    let haystack = vec![
+        entry::new("src/hound.rs".to_string()),
+        entry::new("lib/hounds.rs".to_string()),
+        entry::new("Houndfile".to_string()),
+        entry::new("zzz/qqq.xyz".to_string()),
+        entry::new("vendor/jkl.www".to_string())
+    ];
+    b.iter(|| find("hound", &haystack, 5, &MatchOptions::default()));
 }
 
 #[bench]
 fn bench_similarity(b: &mut Bencher) {
     let entry = entry::new("bloodhound/src/matching.rs".to_string());
-    b.iter(|| entry.similarity("matching.rs"));
+    b.iter(|| entry.similarity("matching.rs", &MatchOptions::default()));
 }
@@ -1,26 +1,37 @@
-use fragment::matching;
+use matching;
+use matching::MatchOptions;
+use matching::entry::{self, Entry};
 use ExclusionPattern;
 use walkdir::{DirEntry, Error, WalkDir};
 use std::path::{Path, PathBuf};
-use indexed_path::IndexedPath;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 
 #[derive(Debug, PartialEq)]
 pub struct Index {
     path: PathBuf,
-    entries: Vec<IndexedPath>,
+    entries: Vec<Entry>,
+
+    /// Whether the caller asked for case-sensitive matching when populating.
+    /// Case folding is a query-time decision now, so this is threaded into the
+    /// `MatchOptions` handed to the matcher rather than baked into the entries.
+    case_sensitive: bool,
 }
 
 impl Index {
     pub fn new(path: PathBuf) -> Index {
         Index {
             path: path,
-            entries: Vec::new()
+            entries: Vec::new(),
+            case_sensitive: false,
         }
     }
 
     /// Finds all files inside and beneath the index path
     /// and adds them to the index entries vector.
     pub fn populate(&mut self, exclusions: Option<Vec<ExclusionPattern>>, case_sensitive: bool) {
+        self.case_sensitive = case_sensitive;
+
         // The entries listed by read_dir include the root index path; we want
         // relative paths, so we get this length so that we can strip it.
         let prefix_length = match self.path.to_str() {
@@ -41,22 +52,68 @@ impl Index {
 
         for entry in filtered_entries {
             relative_entry_path(entry, prefix_length).map(|entry_path| {
-                self.entries.push(
-                    IndexedPath::new(entry_path, case_sensitive)
-                );
+                self.entries.push(entry::new(entry_path));
             });
         }
     }
 
-    pub fn find(&self, term: &str, limit: usize) -> Vec<&Path> {
-        matching::find(term, &self.entries, limit)
+    pub fn find(&self, term: &str, limit: usize) -> Vec<PathBuf> {
+        matching::find_cancellable(term, &self.entries, limit, &self.options(), None)
+            .into_iter()
+            .map(|result| (*result).clone())
+            .collect()
+    }
+
+    /// Like `find`, but abandons the search and returns no (or partial) results
+    /// soon after `cancel` is set by another thread. A live "type to filter"
+    /// picker trips the previous query's flag on each keystroke so the
+    /// in-flight search stops wasting cycles on a query the user has already
+    /// replaced.
+    pub fn find_cancellable(&self, term: &str, limit: usize, cancel: &Arc<AtomicBool>)
+        -> Vec<PathBuf> {
+        matching::find_cancellable(term, &self.entries, limit, &self.options(), Some(cancel))
+            .into_iter()
+            .map(|result| (*result).clone())
+            .collect()
+    }
+
+    /// Like `find`, but tolerant of small typos via a bounded Levenshtein
+    /// automaton, so a mistyped query still surfaces the file the user meant.
+    pub fn find_fuzzy(&self, term: &str, limit: usize) -> Vec<PathBuf> {
+        matching::find_fuzzy(term, &self.entries, limit, &self.options())
             .into_iter()
-            .map(|result| result.as_path())
+            .map(|result| (*result).clone())
             .collect()
     }
 
+    /// Adds a single path to the index, as emitted by a filesystem watcher,
+    /// so callers can stay current without re-walking the whole tree. No-ops
+    /// if the path is already indexed.
+    pub fn insert(&mut self, path: String) {
+        let entry = entry::new(path);
+        if !self.entries.iter().any(|existing| existing.path == entry.path) {
+            self.entries.push(entry);
+        }
+    }
+
+    /// Removes a previously-indexed path in response to a deletion event.
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.retain(|entry| entry.path.as_path() != path);
+    }
+
     pub fn iter(&self) -> impl Iterator<Item=&Path> {
-        self.entries.iter().map(|e| e.as_path())
+        self.entries.iter().map(|e| e.path.as_path())
+    }
+
+    /// Resolves the populate-time case preference into match options: a
+    /// case-sensitive index matches verbatim, while an insensitive one uses
+    /// smart case (folded until the query itself contains an uppercase char).
+    fn options(&self) -> MatchOptions {
+        if self.case_sensitive {
+            MatchOptions::case_sensitive()
+        } else {
+            MatchOptions::default()
+        }
     }
 }
 
@@ -78,43 +135,81 @@ fn relative_entry_path(entry: Result<DirEntry, Error>, prefix_length: usize) ->
 
 #[cfg(test)]
 mod tests {
-    use super::{Index, IndexedPath, ExclusionPattern};
+    use super::{Index, ExclusionPattern};
+    use matching::entry;
     use std::path::{Path, PathBuf};
 
     #[test]
     fn populate_respects_exclusions() {
         let path = PathBuf::from("tests/sample");
         let mut index = Index::new(path);
-        let expected_entries = vec![IndexedPath::new("root_file", true)];
+        let expected_entries = vec![entry::new("root_file".to_string())];
         index.populate(Some(vec![ExclusionPattern::new("**/directory").unwrap()]), true);
 
         assert_eq!(index.entries, expected_entries);
     }
 
     #[test]
-    fn populate_lowercases_entries_when_case_sensitive_is_false() {
-        let path = PathBuf::from("tests/sample");
-        let mut index = Index::new(path);
-        let expected_entries = vec![IndexedPath::new("directory/Capitalized_file", false),
-                                    IndexedPath::new("directory/nested_file", false),
-                                    IndexedPath::new("root_file", false)];
-        index.populate(None, false);
-        index.entries.sort();
+    fn find_matches_case_insensitively_for_lowercase_queries() {
+        let mut index = Index::new(PathBuf::from("."));
+        index.insert("README.md".to_string());
 
-        assert_eq!(index.entries, expected_entries);
+        // Case folding is decided per query now, so an all-lowercase query
+        // still finds the capitalized file without re-indexing.
+        assert_eq!(index.find("readme", 5), vec![PathBuf::from("README.md")]);
     }
 
     #[test]
-    fn populate_lowercases_entries_when_case_sensitive_is_true() {
-        let path = PathBuf::from("tests/sample");
-        let mut index = Index::new(path);
-        let expected_entries = vec![IndexedPath::new("directory/Capitalized_file", true),
-                                    IndexedPath::new("directory/nested_file", true),
-                                    IndexedPath::new("root_file", true)];
-        index.populate(None, true);
-        index.entries.sort();
+    fn find_respects_the_case_sensitive_option() {
+        let mut index = Index::new(PathBuf::from("."));
+        index.insert("Cargo.toml".to_string());
+
+        // A case-sensitive index pins matching to the exact case, so the
+        // lowercase query no longer matches.
+        index.case_sensitive = true;
+        assert!(index.find("cargo", 5).is_empty());
+
+        // Cleared, it falls back to smart case and folds the lowercase query.
+        index.case_sensitive = false;
+        assert_eq!(index.find("cargo", 5), vec![PathBuf::from("Cargo.toml")]);
+    }
 
-        assert_eq!(index.entries, expected_entries);
+    #[test]
+    fn insert_adds_a_single_entry_without_duplicates() {
+        let mut index = Index::new(PathBuf::from("."));
+        index.insert("src/hound.rs".to_string());
+        index.insert("src/hound.rs".to_string());
+
+        assert_eq!(index.iter().collect::<Vec<_>>(), vec![Path::new("src/hound.rs")]);
+    }
+
+    #[test]
+    fn remove_drops_the_matching_entry() {
+        let mut index = Index::new(PathBuf::from("."));
+        index.insert("src/hound.rs".to_string());
+        index.insert("lib/hounds.rs".to_string());
+        index.remove(Path::new("src/hound.rs"));
+
+        assert_eq!(index.iter().collect::<Vec<_>>(), vec![Path::new("lib/hounds.rs")]);
+    }
+
+    #[test]
+    fn find_cancellable_aborts_when_the_flag_is_set() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let mut index = Index::new(PathBuf::from("."));
+        index.insert("src/hound.rs".to_string());
+
+        // A flag already tripped by a newer query aborts the search before it
+        // spends any work, yielding no results.
+        let cancel = Arc::new(AtomicBool::new(true));
+        assert!(index.find_cancellable("hound", 5, &cancel).is_empty());
+
+        // Cleared, the same search proceeds normally.
+        cancel.store(false, Ordering::Relaxed);
+        assert_eq!(index.find_cancellable("hound", 5, &cancel),
+                   vec![PathBuf::from("src/hound.rs")]);
     }
 
     #[test]
@@ -126,6 +221,6 @@ mod tests {
         let limit = 5;
         let results = index.find(term, limit);
 
-        assert_eq!(results, vec![Path::new("root_file")]);
+        assert_eq!(results, vec![PathBuf::from("root_file")]);
     }
 }
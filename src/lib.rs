@@ -1,9 +1,10 @@
-extern crate fragment;
 extern crate glob;
+extern crate unicode_normalization;
 extern crate walkdir;
 
+pub mod matching;
 mod index;
-mod indexed_path;
 
 pub use index::Index;
+pub use matching::entry;
 pub use glob::Pattern as ExclusionPattern;
@@ -0,0 +1,86 @@
+/// A compact set of the character _classes_ present in a string, used to
+/// cheaply reject entries that can't possibly contain every character in a
+/// query before paying for the full fragment walk.
+///
+/// The fast alphabet (`a`–`z`, `0`–`9`, and a handful of common path
+/// delimiters) is 41 classes, which pack comfortably into a single `u64`
+/// bitmask. Any character outside that set trips the `overflow` flag, at which
+/// point the bag can no longer make a confident rejection and the caller must
+/// fall back to the full scorer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CharBag {
+    bits: u64,
+    overflow: bool,
+}
+
+impl CharBag {
+    /// Builds a bag from the (already case-folded) characters of `subject`.
+    pub fn from_str(subject: &str) -> CharBag {
+        let mut bag = CharBag{ bits: 0, overflow: false };
+        for subject_char in subject.chars() {
+            bag.insert(subject_char);
+        }
+
+        bag
+    }
+
+    fn insert(&mut self, subject_char: char) {
+        match bit_index(subject_char) {
+            Some(index) => self.bits |= 1u64 << index,
+            None => self.overflow = true,
+        }
+    }
+
+    /// Returns true when this bag can't prove `query` is missing a character,
+    /// i.e. every bit set in `query` is also set here. An overflow on either
+    /// side is inconclusive, so we conservatively keep the entry and let the
+    /// full scorer decide.
+    pub fn is_superset_of(&self, query: &CharBag) -> bool {
+        if self.overflow || query.overflow {
+            return true;
+        }
+
+        self.bits & query.bits == query.bits
+    }
+}
+
+/// Maps a character in the fast alphabet to its bit position, or `None` for
+/// anything that must be handled by the full scorer.
+fn bit_index(subject_char: char) -> Option<usize> {
+    match subject_char {
+        'a'..='z' => Some(subject_char as usize - 'a' as usize),
+        '0'..='9' => Some(26 + subject_char as usize - '0' as usize),
+        '/' => Some(36),
+        '.' => Some(37),
+        '_' => Some(38),
+        '-' => Some(39),
+        ' ' => Some(40),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CharBag;
+
+    #[test]
+    fn superset_accepts_a_subset_query() {
+        let entry = CharBag::from_str("src/hound.rs");
+        let query = CharBag::from_str("hound");
+        assert!(entry.is_superset_of(&query));
+    }
+
+    #[test]
+    fn superset_rejects_a_query_with_a_missing_character() {
+        let entry = CharBag::from_str("src/hound.rs");
+        let query = CharBag::from_str("zzz");
+        assert!(!entry.is_superset_of(&query));
+    }
+
+    #[test]
+    fn superset_is_conservative_when_the_query_overflows() {
+        let entry = CharBag::from_str("src/hound.rs");
+        let query = CharBag::from_str("héllo");
+        assert!(entry.is_superset_of(&query));
+    }
+}
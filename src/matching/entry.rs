@@ -1,25 +1,134 @@
-use matching::fragment;
+use matching::char_bag::CharBag;
 use matching::fragment::Fragment;
+use matching::levenshtein::Automaton;
+use matching::MatchOptions;
+use matching::normalize::{normalize, normalize_with_map};
+use matching::score::MatchScore;
+use std::fmt;
 use std::path::PathBuf;
 use std::collections::hash_map::HashMap;
 
-#[derive(PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Entry {
     pub path: PathBuf,
     pub index: HashMap<char, Vec<usize>>,
+    pub lowercase_index: HashMap<char, Vec<usize>>,
+    path_string: String,
+    lowercase_path_string: String,
+
+    /// For each character of `lowercase_path_string`, the char index of the
+    /// original `path_string` character it was derived from. Normalization
+    /// isn't length-preserving, so this maps positions found against the
+    /// normalized representation back onto the original path for highlighting.
+    position_map: Vec<usize>,
+
+    /// The set of character classes present in the (case-folded) path, used
+    /// to cheaply skip entries that can't contain every query character
+    /// before running the full fragment scorer.
+    char_bag: CharBag,
+
+    /// Whether the normalized representation strips accents. Stored so the
+    /// incoming query can be folded with the exact same rules as the index.
+    strip_accents: bool,
 }
 
 impl Entry {
-    /// Compares the query string to the entry,
-    /// and returns a score between 0 and 1.
-    pub fn similarity(&self, query: &str) -> f32 {
-        // Exact matches produce a perfect score.
-        if query == self.path.to_string_lossy() {
-            return 1.0;
+    /// The set of character classes in this entry's path. Callers use it to
+    /// prefilter the haystack against the query's bag before scoring.
+    pub fn char_bag(&self) -> &CharBag {
+        &self.char_bag
+    }
+
+    /// Scores the entry against a query that may contain typos, using a
+    /// Levenshtein automaton shared across the whole haystack (it's compiled
+    /// once per query in `find`, not once per entry). The realized edit
+    /// distance becomes the composite score's `typos` criterion, which
+    /// dominates the ordering so that exact and fewer-edit matches rank first.
+    ///
+    /// A distance-zero match falls through to the exact fragment scorer so
+    /// clean input keeps its precise ranking; entries that can't be matched
+    /// within the automaton's bound return `None` and leave the result set.
+    ///
+    /// Typo-tolerant matches (distance > 0) report no highlight positions: the
+    /// automaton yields only the best edit distance, not the aligned
+    /// characters, and reconstructing them would mean re-running the alignment.
+    /// Callers treat an empty position set as "nothing to highlight", so the
+    /// match still ranks and displays — just without emphasized characters.
+    pub fn similarity_fuzzy(&self, query: &str, automaton: &Automaton, options: &MatchOptions)
+        -> Option<(MatchScore, Vec<usize>)> {
+        match automaton.search(&self.lowercase_path_string) {
+            None => None,
+            Some(0) => Some(self.similarity(query, options)),
+            Some(distance) => Some((
+                MatchScore{
+                    typos: distance,
+                    matched_chars: query.chars().count(),
+                    proximity: 0,
+                    boundary_bonus: 0,
+                    case_bonus: 0,
+                    total_length: self.lowercase_path_string.chars().count(),
+                    offset: 0,
+                },
+                // No aligned positions are available for a typo match; see the
+                // method doc. Callers render this as an un-highlighted hit.
+                Vec::new(),
+            )),
         }
+    }
+
+    /// Compares the query string to the entry, returning a composite
+    /// `MatchScore` along with the sorted, de-duplicated set of character
+    /// positions in the entry path that the winning match fragments covered.
+    /// Callers use the positions to highlight the matched characters in each
+    /// result.
+    ///
+    /// `options` decides how letter case is treated for this query — see
+    /// `MatchOptions` for the smart-case default. Case-insensitive matching
+    /// runs against the pre-folded representation, while a case-sensitive query
+    /// matches the path verbatim; positions already sharing the query's exact
+    /// case earn a small `case_bonus` tiebreaker either way.
+    pub fn similarity(&self, query: &str, options: &MatchOptions) -> (MatchScore, Vec<usize>) {
+        // Keep the untouched query around: the match gate below compares the
+        // active, case/accent-respecting representation against a maximally
+        // folded one to tell a pure case/accent mismatch apart from a genuine
+        // partial match.
+        let raw_query = query;
+
+        // Pick the representation (and matching char index) that satisfies the
+        // caller's case options for this particular query.
+        let case_sensitive = options.is_case_sensitive(query);
+        let (subject, index) = if case_sensitive {
+            (&self.path_string, &self.index)
+        } else {
+            (&self.lowercase_path_string, &self.lowercase_index)
+        };
+        // Case-insensitive matching folds the query through the same Unicode
+        // normalization that built the indexed representation.
+        let query = if case_sensitive {
+            query.to_string()
+        } else {
+            normalize(query, self.strip_accents)
+        };
+        let query = query.as_str();
 
         // Pre-calculate the path length as we'll be using it frequently.
-        let path_length = self.path.to_string_lossy().chars().count();
+        let path_length = subject.chars().count();
+
+        // Exact matches cover every character with no gaps.
+        if query == subject {
+            let positions: Vec<usize> = (0..path_length).collect();
+            let (proximity, boundary_bonus, offset) = positional_metrics(subject, &positions);
+            let score = MatchScore{
+                typos: 0,
+                matched_chars: path_length,
+                proximity: proximity,
+                boundary_bonus: boundary_bonus,
+                case_bonus: self.case_bonus(case_sensitive, subject, &positions),
+                total_length: path_length,
+                offset: offset,
+            };
+            return (score, self.map_positions(case_sensitive, positions));
+        }
 
         // We track fragment/substring matches, which have a greater weight than
         // a simple sum of individual character occurrences in the entry path.
@@ -31,7 +140,7 @@ impl Entry {
 
         // Look for the query's character in the path's index.
         for query_char in query.chars() {
-            match self.index.get(&query_char) {
+            match index.get(&query_char) {
                 Some(occurrences) => {
                     // Initially, we'll assume that none of the occurrences
                     // of this character have been tracked as fragments.
@@ -61,7 +170,7 @@ impl Entry {
 
                     // Create fragment matches for any unaccounted occurrences.
                     for occurrence_index in unaccounted_occurrences.iter() {
-                        match_fragments.push(fragment::new(*occurrence_index));
+                        match_fragments.push(Fragment::new(*occurrence_index));
                     }
 
                 },
@@ -74,25 +183,202 @@ impl Entry {
             }
         }
 
-        // Determine the percentage of characters in the query string that
-        // are in the entry, using the non-existent count we've calculated.
-        let non_existence_penalty =
-            // Guard against a potential arithmetic overflow here.
-            if non_existent_char_count >= path_length {
-                return 0.0f32
+        // Distinguish a pure case/accent mismatch from an honest partial
+        // match. A needle character missing from the active representation but
+        // present once the path is fully folded (lowercased and accent
+        // stripped) only failed on case or accent — the sort of near-miss a
+        // case-sensitive or strict query is meant to reject. A character
+        // missing even from the folded form is simply absent, and the match is
+        // scored as a weaker partial. So the entry drops out when the active
+        // representation hides more characters than the folded one.
+        let canonical_path = normalize(&self.path_string, true);
+        let canonical_absent = normalize(raw_query, true)
+            .chars()
+            .filter(|&query_char| !canonical_path.contains(query_char))
+            .count();
+        if non_existent_char_count > canonical_absent {
+            return (MatchScore::none(), Vec::new());
+        }
+
+        // When accents are kept, the decomposed representation still holds
+        // the bare base letter of an accented character, so the walk above
+        // lets "e" match "é". Compare the recomposed forms as well, so the
+        // entry drops out when they hide more query characters than the
+        // folded one.
+        if !self.strip_accents {
+            use unicode_normalization::UnicodeNormalization;
+            let strict_path: String = self.lowercase_path_string.nfc().collect();
+            let strict_absent = normalize(raw_query, false)
+                .nfc()
+                .filter(|&query_char| !strict_path.contains(query_char))
+                .count();
+            if strict_absent > canonical_absent {
+                return (MatchScore::none(), Vec::new());
+            }
+        }
+
+        // Collect the path indices covered by the match fragments that
+        // contributed to the score, sorted and de-duplicated so callers can
+        // render contiguous highlights.
+        let mut positions: Vec<usize> =
+            match_fragments.iter().flat_map(|fragment| fragment.range()).collect();
+        positions.sort();
+        positions.dedup();
+
+        // With nothing matched there's no meaningful score to report.
+        if positions.is_empty() {
+            return (MatchScore::none(), Vec::new());
+        }
+
+        // The positional criteria are derived from the path positions the
+        // query's characters land on *in query order*, not the sorted
+        // highlight set: a scrambled query ("nuoh" against "hound") walks the
+        // path backwards and should score worse than an in-order one, which a
+        // sorted set would hide.
+        let ordered_positions = needle_order_positions(&query, index);
+        let (proximity, boundary_bonus, offset) = positional_metrics(subject, &ordered_positions);
+
+        let score = MatchScore{
+            // The exact fragment scorer never consumes edits.
+            typos: 0,
+            // Count the needle characters that found a home in the path, not
+            // the distinct path positions covered, so a repeated query
+            // character isn't under- or over-counted against it.
+            matched_chars: query.chars().count() - non_existent_char_count,
+            proximity: proximity,
+            boundary_bonus: boundary_bonus,
+            case_bonus: self.case_bonus(case_sensitive, subject, &positions),
+            total_length: path_length,
+            offset: offset,
+        };
+
+        (score, self.map_positions(case_sensitive, positions))
+    }
+
+    /// Translates match positions into indices the caller can use to highlight
+    /// the original path. Under case-sensitive matching the positions already
+    /// index the original `path_string`, so they're returned untouched; under
+    /// case-insensitive matching they index the normalized representation and
+    /// are mapped back through `position_map` (then re-sorted and de-duplicated,
+    /// since normalization can collapse several normalized characters onto one
+    /// original character).
+    fn map_positions(&self, case_sensitive: bool, positions: Vec<usize>) -> Vec<usize> {
+        if case_sensitive {
+            return positions;
+        }
+
+        let mut mapped: Vec<usize> =
+            positions.iter().map(|&position| self.position_map[position]).collect();
+        mapped.sort();
+        mapped.dedup();
+        mapped
+    }
+
+    /// Counts how many matched positions already share the query's exact case.
+    /// Under case-sensitive matching every match is exact by definition; under
+    /// case-insensitive matching a position is exact when the original path
+    /// character was already lowercase (the query having been folded to
+    /// lowercase). When normalization changed the string's length — so the
+    /// case-preserving and folded representations no longer align index for
+    /// index — we conservatively award no bonus rather than risk a mismatch.
+    fn case_bonus(&self, case_sensitive: bool, subject: &str, positions: &[usize]) -> usize {
+        if case_sensitive {
+            return positions.len();
+        }
+
+        let original: Vec<char> = self.path_string.chars().collect();
+        let folded: Vec<char> = subject.chars().collect();
+        if original.len() != folded.len() {
+            return 0;
+        }
+
+        positions.iter().filter(|&&position| original[position] == folded[position]).count()
+    }
+}
+
+/// Walks the match positions in query order and derives the three positional
+/// criteria used by the composite score: `proximity` (the total gap between
+/// consecutive matched needle characters), `boundary_bonus` (how many matches
+/// begin a new word) and `offset` (the first matched index). Tightly-clustered,
+/// in-order, boundary-aligned matches thereby beat scattered or scrambled ones
+/// covering the same characters. A needle character landing *before* its
+/// predecessor (an out-of-order match) is penalized by that backward distance.
+fn positional_metrics(subject: &str, positions: &[usize]) -> (usize, usize, usize) {
+    let chars: Vec<char> = subject.chars().collect();
+    let mut proximity = 0;
+    let mut boundary_bonus = 0;
+    let mut previous: Option<usize> = None;
+
+    for &position in positions {
+        // Accumulate the gap since the previous matched character.
+        if let Some(previous) = previous {
+            proximity += if position > previous {
+                position - previous - 1
             } else {
-                (path_length - non_existent_char_count) as f32 / path_length as f32
+                previous - position + 1
             };
+        }
+
+        // Reward matches that begin a new word.
+        if begins_word(&chars, position) {
+            boundary_bonus += 1;
+        }
+
+        previous = Some(position);
+    }
+
+    // An empty-string exact match (query == subject == "") covers no
+    // positions, so default the offset rather than indexing an empty slice.
+    (proximity, boundary_bonus, positions.first().cloned().unwrap_or(0))
+}
+
+/// Resolves, for each query character in turn, the path position it matches,
+/// yielding the positions in query order (not sorted). Each character takes the
+/// earliest occurrence after the previous match to stay in order; when the
+/// query is scrambled and no later occurrence exists it falls back to the
+/// earliest overall, which `positional_metrics` then penalizes as a backward
+/// step. Characters absent from the path are skipped.
+fn needle_order_positions(query: &str, index: &HashMap<char, Vec<usize>>) -> Vec<usize> {
+    let mut positions = Vec::new();
+    let mut previous: Option<usize> = None;
+
+    for query_char in query.chars() {
+        if let Some(occurrences) = index.get(&query_char) {
+            let next = occurrences.iter().cloned()
+                .find(|&occurrence| previous.map_or(true, |p| occurrence > p))
+                .or_else(|| occurrences.iter().cloned().min());
+
+            if let Some(position) = next {
+                positions.push(position);
+                previous = Some(position);
+            }
+        }
+    }
+
+    positions
+}
 
-        // Calculate an exponentially-scaled score based on fragment lengths.
-        let fragment_score = match_fragments.iter().fold(0, |acc, ref fragment| {
-            acc + fragment.length.pow(2)
-        });
+/// Returns true when the character at `position` starts a new word: it sits at
+/// the start of the path, immediately follows a separator (`/`, `_`, `-`,
+/// `.`), or marks a lowercase→uppercase camelCase transition.
+fn begins_word(chars: &[char], position: usize) -> bool {
+    if position == 0 {
+        return true;
+    }
+
+    let previous = chars[position - 1];
+    match previous {
+        '/' | '_' | '-' | '.' => true,
+        _ => previous.is_lowercase() && chars[position].is_uppercase(),
+    }
+}
 
-        // Calculate and return the similarity value. The path_length division
-        // is used to offset the increased fragment score probability for
-        // larger entry paths.
-        fragment_score as f32 * non_existence_penalty / path_length as f32
+/// An `Entry`'s string representation is its case-preserving path, letting it
+/// flow through the generic, `ToString`-based matching engine like any other
+/// matchable item.
+impl fmt::Display for Entry {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.path_string)
     }
 }
 
@@ -113,66 +399,207 @@ fn index_path(path: &str) -> HashMap<char, Vec<usize>> {
 }
 
 pub fn new(path: String) -> Entry {
+    // Accent-insensitive matching is the friendlier default; callers that
+    // want strict matching reach for `new_with_normalization`.
+    new_with_normalization(path, true)
+}
+
+pub fn new_with_normalization(path: String, strip_accents: bool) -> Entry {
+    // Build a normalized, case-folded representation (and its index)
+    // alongside the case-preserving one so smart case can be resolved per
+    // query. The original `path` stays byte-exact for filesystem access.
+    let (lowercase_path, position_map) = normalize_with_map(&path, strip_accents);
+
     Entry{
-        // Build the index before we transfer ownership of path.
+        // Build the indexes and character bag before we transfer ownership
+        // of the strings. The bag is keyed off the normalized form so the
+        // prefilter stays a conservative superset regardless of query case.
         index: index_path(&path),
-        path: PathBuf::from(path),
+        lowercase_index: index_path(&lowercase_path),
+        char_bag: CharBag::from_str(&lowercase_path),
+        path: PathBuf::from(&path),
+        path_string: path,
+        lowercase_path_string: lowercase_path,
+        position_map: position_map,
+        strip_accents: strip_accents,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::new;
+    use matching::MatchOptions;
 
     #[test]
     fn similarity_correctly_scores_perfect_matches() {
         let entry = new("src/hound.rs".to_string());
-        assert_eq!(entry.similarity("src/hound.rs"), 1.0);
+        let score = entry.similarity("src/hound.rs", &MatchOptions::default()).0;
+
+        // Every character is matched, with no typos.
+        assert_eq!(score.typos, 0);
+        assert_eq!(score.matched_chars, "src/hound.rs".chars().count());
     }
 
     #[test]
     fn similarity_correctly_scores_completely_different_terms() {
         let entry = new("src".to_string());
-        assert_eq!(entry.similarity("lib"), 0.0);
+        assert!(!entry.similarity("lib", &MatchOptions::default()).0.is_match());
     }
 
     #[test]
-    fn similarity_scores_based_on_term_length() {
-        let long_entry = new("hound library".to_string());
-        let differing_length_score = long_entry.similarity("houn");
-
-        // Don't use a perfect match, since those product a perfect score.
-        let short_entry = new("hound".to_string());
-        let same_length_score = short_entry.similarity("houn");
+    fn similarity_prefers_more_matched_characters() {
+        let entry = new("hound".to_string());
+        let more = entry.similarity("houn", &MatchOptions::default()).0;
+        let fewer = entry.similarity("hou", &MatchOptions::default()).0;
 
-        assert!(same_length_score > differing_length_score);
+        // A lower composite score is a better match; covering more of the
+        // path's characters wins once typos tie.
+        assert!(more < fewer);
     }
 
     #[test]
     fn similarity_score_increases_for_consecutive_matches() {
         let entry = new("hound".to_string());
 
-        // Don't use a perfect match, since those product a perfect score.
-        let properly_ordered_score = entry.similarity(" houn");
+        // Don't use a perfect match, since those produce a perfect score.
+        let properly_ordered_score = entry.similarity(" houn", &MatchOptions::default()).0;
 
-        let improperly_ordered_score = entry.similarity("nuoh");
-        assert!(properly_ordered_score > improperly_ordered_score);
+        let improperly_ordered_score = entry.similarity("nuoh", &MatchOptions::default()).0;
+
+        // Contiguous matches have tighter proximity and rank ahead.
+        assert!(properly_ordered_score < improperly_ordered_score);
     }
 
     #[test]
     fn similarity_score_decreases_for_non_matching_characters() {
         let entry = new("hound".to_string());
 
-        // Don't use a perfect match, since those product a perfect score.
-        let non_matching_score = entry.similarity("houns");
+        // "houx" has a character the path lacks, so it matches fewer chars
+        // than the fully-covered "houn" of the same length.
+        let non_matching_score = entry.similarity("houx", &MatchOptions::default()).0;
+        let fully_matching_score = entry.similarity("houn", &MatchOptions::default()).0;
 
-        let subset_score = entry.similarity("houn");
-        assert!(subset_score > non_matching_score);
+        assert!(fully_matching_score < non_matching_score);
     }
 
     #[test]
-    fn similarity_score_is_zero_for_larger_query_with_no_matching_characters() {
+    fn similarity_score_is_empty_for_larger_query_with_no_matching_characters() {
         let entry = new("amp".to_string());
-        assert_eq!(entry.similarity("hound"), 0.0f32);
+        assert!(!entry.similarity("hound", &MatchOptions::default()).0.is_match());
+    }
+
+    #[test]
+    fn similarity_returns_matched_positions_for_highlighting() {
+        let entry = new("hound".to_string());
+
+        // The "oun" fragment covers indices 1, 2 and 3 of "hound".
+        assert_eq!(entry.similarity("oun", &MatchOptions::default()).1, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn similarity_handles_empty_exact_match_without_panicking() {
+        let entry = new(String::new());
+
+        // An empty query against an empty path is an exact match covering no
+        // positions; the scorer must not index into the empty position set.
+        let (score, positions) = entry.similarity("", &MatchOptions::default());
+        assert_eq!(score.offset, 0);
+        assert!(positions.is_empty());
+    }
+
+    #[test]
+    fn similarity_returns_positions_in_original_path_space() {
+        // The combining accent is stripped during normalization, shrinking the
+        // scored representation. The returned positions must still index the
+        // original path, where "x" sits at char index 2, not the normalized
+        // index 1 it lands on after the accent is dropped.
+        let entry = new("e\u{301}x".to_string());
+        assert_eq!(entry.similarity("x", &MatchOptions::default()).1, vec![2]);
+    }
+
+    #[test]
+    fn similarity_prefers_clustered_matches_over_scattered_ones() {
+        let clustered = new("srchound.rs".to_string());
+        let scattered = new("src/hound.rs".to_string());
+
+        // The query's characters sit contiguously in the clustered path but
+        // are split by a separator in the scattered one, so the clustered
+        // match has tighter proximity.
+        assert!(clustered.similarity("srchound", &MatchOptions::default()).0 < scattered.similarity("srchound", &MatchOptions::default()).0);
+    }
+
+    #[test]
+    fn similarity_rewards_word_boundary_matches() {
+        let boundary = new("src/hound.rs".to_string());
+        let interior = new("xsrchound.rs".to_string());
+
+        // Both paths contain "hound" contiguously, but only in "src/hound.rs"
+        // does it begin a word (right after the separator).
+        assert!(boundary.similarity("hound", &MatchOptions::default()).0 < interior.similarity("hound", &MatchOptions::default()).0);
+    }
+
+    #[test]
+    fn similarity_matches_across_accents_by_default() {
+        let entry = new("Café.txt".to_string());
+
+        // Accent-stripping normalization lets an unaccented query match.
+        assert!(entry.similarity("cafe", &MatchOptions::default()).0.is_match());
+    }
+
+    #[test]
+    fn similarity_maps_accented_positions_back_to_the_original_path() {
+        let entry = new("Café.txt".to_string());
+
+        // The accented character is one scoring char ("e" after stripping),
+        // so the match must still land on its original index (3), not drift.
+        assert_eq!(entry.similarity("cafe", &MatchOptions::default()).1, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn similarity_respects_strict_matching_when_accents_are_kept() {
+        use super::new_with_normalization;
+        let entry = new_with_normalization("Café.txt".to_string(), false);
+
+        // With accent-stripping disabled, the unaccented query can't cover
+        // the accented character.
+        assert!(!entry.similarity("cafe", &MatchOptions::default()).0.is_match());
+    }
+
+    #[test]
+    fn similarity_fuzzy_folds_edit_distance_into_the_typos_criterion() {
+        use matching::levenshtein::Automaton;
+        let entry = new("matching.rs".to_string());
+
+        // "machng" is two deletions away from "matching".
+        let automaton = Automaton::new("machng", 2);
+        let (score, _) = entry.similarity_fuzzy("machng", &automaton, &MatchOptions::default()).unwrap();
+        assert_eq!(score.typos, 2);
+    }
+
+    #[test]
+    fn similarity_fuzzy_rejects_entries_beyond_the_bound() {
+        use matching::levenshtein::Automaton;
+        let entry = new("matching.rs".to_string());
+
+        let automaton = Automaton::new("xyz", 0);
+        assert!(entry.similarity_fuzzy("xyz", &automaton, &MatchOptions::default()).is_none());
+    }
+
+    #[test]
+    fn similarity_matches_case_insensitively_for_lowercase_queries() {
+        let entry = new("README.md".to_string());
+
+        // An all-lowercase query folds case and still finds the file.
+        assert!(entry.similarity("readme", &MatchOptions::default()).0.is_match());
+    }
+
+    #[test]
+    fn similarity_matches_case_sensitively_when_query_has_uppercase() {
+        let capitalized = new("Cargo.toml".to_string());
+        let lowercased = new("cargo.toml".to_string());
+
+        // An uppercase character in the query pins matching to that case.
+        assert!(capitalized.similarity("Cargo", &MatchOptions::default()).0.is_match());
+        assert!(!lowercased.similarity("Cargo", &MatchOptions::default()).0.is_match());
     }
 }
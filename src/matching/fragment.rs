@@ -22,6 +22,12 @@ impl Fragment {
     pub fn increase_length(&mut self) {
         self.length += 1;
     }
+
+    /// The range of path indices covered by this fragment. Used to collect
+    /// the matched character positions for result highlighting.
+    pub fn range(&self) -> ::std::ops::Range<usize> {
+        self.index..self.index + self.length
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,239 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A query compiled into a bounded Levenshtein automaton.
+///
+/// The query is compiled once in `find` and every entry's path string is
+/// streamed through it; nothing is rebuilt per haystack entry. The automaton is
+/// a deterministic transition system whose states are the reachable
+/// characteristic vectors of the edit-distance computation (the two trailing
+/// rows of the recurrence, clamped to the distance bound so the state set stays
+/// finite). Transitions are keyed on `(state, input-char bitmask)` — the
+/// bitmask recording which of the query's characters the input char matches —
+/// and are solved lazily and memoized, so each distinct transition is computed
+/// only once no matter how many entries exercise it.
+///
+/// The automaton performs approximate substring matching: it reports the
+/// smallest edit distance (insertions, deletions, substitutions or adjacent
+/// transpositions) between the query and any substring of the subject, or
+/// `None` when that distance exceeds the bound.
+pub struct Automaton {
+    query: Vec<char>,
+    max_distance: usize,
+
+    /// Costs are clamped here so that anything past the budget collapses onto a
+    /// single "dead" value, bounding the number of reachable states.
+    cap: u8,
+
+    /// The id of the start state in `states`.
+    start: usize,
+
+    /// Memoized characteristic bitmask for each input character seen so far.
+    char_masks: RefCell<HashMap<char, u64>>,
+
+    /// Interned states, indexed by id, and the reverse lookup used to dedupe.
+    states: RefCell<Vec<State>>,
+    ids: RefCell<HashMap<State, usize>>,
+
+    /// The shared transition table: `(state id, input bitmask) -> state id`.
+    transitions: RefCell<HashMap<(usize, u64), usize>>,
+}
+
+/// A single automaton state: the two trailing rows of the recurrence (clamped
+/// to the distance bound) plus the previous input char's bitmask, which is all
+/// the history an adjacent transposition needs.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct State {
+    before_previous: Vec<u8>,
+    previous: Vec<u8>,
+    previous_mask: u64,
+}
+
+impl Automaton {
+    pub fn new(query: &str, max_distance: usize) -> Automaton {
+        let query: Vec<char> = query.chars().collect();
+        let columns = query.len() + 1;
+        let cap = (max_distance + 1) as u8;
+        let clamp = |value: usize| value.min(max_distance + 1) as u8;
+
+        // The start state mirrors the recurrence's seed rows: a leading row of
+        // zeros (so a match may begin at any offset) and an ascending row that
+        // charges one edit per unmatched query character.
+        let start_state = State{
+            before_previous: vec![0; columns],
+            previous: (0..columns).map(&clamp).collect(),
+            previous_mask: 0,
+        };
+
+        let automaton = Automaton{
+            query: query,
+            max_distance: max_distance,
+            cap: cap,
+            start: 0,
+            char_masks: RefCell::new(HashMap::new()),
+            states: RefCell::new(Vec::new()),
+            ids: RefCell::new(HashMap::new()),
+            transitions: RefCell::new(HashMap::new()),
+        };
+        automaton.intern(start_state);
+
+        automaton
+    }
+
+    /// Streams `subject` through the automaton, returning the realized edit
+    /// distance of the best-matching substring when it falls within the
+    /// bound, or `None` otherwise.
+    pub fn search(&self, subject: &str) -> Option<usize> {
+        let last = self.query.len();
+        let mut state = self.start;
+        let mut best = self.states.borrow()[state].previous[last] as usize;
+
+        for subject_char in subject.chars() {
+            let mask = self.mask(subject_char);
+            state = self.step(state, mask);
+            best = best.min(self.states.borrow()[state].previous[last] as usize);
+        }
+
+        if best <= self.max_distance {
+            Some(best)
+        } else {
+            None
+        }
+    }
+
+    /// The characteristic bitmask of `subject_char`: bit `i` is set when it
+    /// equals the query's `i`th character. Memoized so repeated characters
+    /// across the haystack don't re-scan the query.
+    fn mask(&self, subject_char: char) -> u64 {
+        if let Some(&mask) = self.char_masks.borrow().get(&subject_char) {
+            return mask;
+        }
+
+        let mut mask = 0u64;
+        for (index, &query_char) in self.query.iter().enumerate() {
+            if query_char == subject_char {
+                mask |= 1u64 << index;
+            }
+        }
+        self.char_masks.borrow_mut().insert(subject_char, mask);
+
+        mask
+    }
+
+    /// Advances from `state` on an input char with characteristic `mask`,
+    /// consulting (and populating) the shared transition table so each
+    /// `(state, mask)` pair is only ever solved once.
+    fn step(&self, state: usize, mask: u64) -> usize {
+        if let Some(&next) = self.transitions.borrow().get(&(state, mask)) {
+            return next;
+        }
+
+        let columns = self.query.len() + 1;
+        let (before_previous, previous, previous_mask) = {
+            let states = self.states.borrow();
+            let state = &states[state];
+            (state.before_previous.clone(), state.previous.clone(), state.previous_mask)
+        };
+
+        let mut current = vec![0u8; columns];
+        for column in 1..columns {
+            let substitution_cost = if mask & (1u64 << (column - 1)) != 0 { 0 } else { 1 };
+
+            let mut cost = (current[column - 1] as usize + 1)
+                .min(previous[column] as usize + 1)
+                .min(previous[column - 1] as usize + substitution_cost);
+
+            // Adjacent transposition (Damerau), e.g. "cagro" -> "cargo": the
+            // previous input char matched this query char and the current one
+            // matches the preceding query char.
+            if column > 1
+                && previous_mask & (1u64 << (column - 1)) != 0
+                && mask & (1u64 << (column - 2)) != 0
+            {
+                cost = cost.min(before_previous[column - 2] as usize + 1);
+            }
+
+            current[column] = cost.min(self.cap as usize) as u8;
+        }
+
+        let next = self.intern(State{
+            before_previous: previous,
+            previous: current,
+            previous_mask: mask,
+        });
+        self.transitions.borrow_mut().insert((state, mask), next);
+
+        next
+    }
+
+    /// Interns `state`, returning its stable id and reusing an existing id when
+    /// the state has already been seen.
+    fn intern(&self, state: State) -> usize {
+        if let Some(&id) = self.ids.borrow().get(&state) {
+            return id;
+        }
+
+        let id = {
+            let mut states = self.states.borrow_mut();
+            let id = states.len();
+            states.push(state.clone());
+            id
+        };
+        self.ids.borrow_mut().insert(state, id);
+
+        id
+    }
+}
+
+/// The edit-distance bound for a query of `length` characters. Short queries
+/// tolerate no typos (they're too ambiguous), with the budget growing as the
+/// query gets longer.
+pub fn max_distance(length: usize) -> usize {
+    if length <= 3 {
+        0
+    } else if length <= 7 {
+        1
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Automaton, max_distance};
+
+    #[test]
+    fn search_finds_a_single_deletion() {
+        let automaton = Automaton::new("hund", 1);
+        assert_eq!(automaton.search("hound"), Some(1));
+    }
+
+    #[test]
+    fn search_finds_a_transposition() {
+        let automaton = Automaton::new("cagro", 1);
+        assert_eq!(automaton.search("cargo.toml"), Some(1));
+    }
+
+    #[test]
+    fn search_rejects_matches_beyond_the_bound() {
+        let automaton = Automaton::new("xyz", 0);
+        assert_eq!(automaton.search("hound"), None);
+    }
+
+    #[test]
+    fn search_reuses_the_shared_transition_table_across_subjects() {
+        // A single compiled automaton scores many subjects; the second search
+        // exercises the same memoized transitions as the first.
+        let automaton = Automaton::new("hund", 1);
+        assert_eq!(automaton.search("hound"), Some(1));
+        assert_eq!(automaton.search("hund"), Some(0));
+        assert_eq!(automaton.search("lib/hounds.rs"), Some(1));
+    }
+
+    #[test]
+    fn max_distance_scales_with_query_length() {
+        assert_eq!(max_distance(3), 0);
+        assert_eq!(max_distance(5), 1);
+        assert_eq!(max_distance(10), 2);
+    }
+}
@@ -1,57 +1,317 @@
 pub mod entry;
+mod char_bag;
 mod fragment;
+mod levenshtein;
+mod normalize;
+mod score;
 
-use std::path::PathBuf;
 use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use matching::char_bag::CharBag;
 use matching::entry::Entry;
+use matching::levenshtein::Automaton;
+use matching::score::MatchScore;
+
+/// How often (in scored entries) the cancellable search checks its cancel
+/// flag. Checking every iteration would add atomic-load overhead to the hot
+/// loop; checking in batches keeps it responsive without the cost.
+const CANCELLATION_INTERVAL: usize = 256;
+
+/// Controls how a search treats letter case. The default is "smart case":
+/// matching stays case-insensitive until the query itself contains an
+/// uppercase character, at which point it becomes case-sensitive. Setting
+/// `case_sensitive` forces sensitivity regardless of the query, while clearing
+/// both flags makes every comparison case-insensitive.
+#[derive(Clone, Copy, Debug)]
+pub struct MatchOptions {
+    /// Force case-sensitive matching, overriding `smart_case`.
+    pub case_sensitive: bool,
+
+    /// Enable smart case: insensitive until the query has an uppercase char.
+    pub smart_case: bool,
+}
+
+impl Default for MatchOptions {
+    fn default() -> MatchOptions {
+        MatchOptions{ case_sensitive: false, smart_case: true }
+    }
+}
+
+impl MatchOptions {
+    /// Always case-insensitive, regardless of the query.
+    pub fn case_insensitive() -> MatchOptions {
+        MatchOptions{ case_sensitive: false, smart_case: false }
+    }
+
+    /// Always case-sensitive, regardless of the query.
+    pub fn case_sensitive() -> MatchOptions {
+        MatchOptions{ case_sensitive: true, smart_case: false }
+    }
+
+    /// Resolves, for a concrete `query`, whether matching should be
+    /// case-sensitive under these options.
+    pub fn is_case_sensitive(&self, query: &str) -> bool {
+        self.case_sensitive || (self.smart_case && query.chars().any(|c| c.is_uppercase()))
+    }
+}
 
+/// A single match, owning the matched item. `Result` dereferences to the item
+/// so callers can treat it transparently as a `&T` (e.g. read a `PathBuf`,
+/// command name or buffer title) while still reaching the match `score` and
+/// highlight `positions`.
 #[derive(Debug, PartialEq)]
-pub struct Result {
-    pub path: PathBuf,
-    score: f32,
+pub struct Result<T> {
+    item: T,
+    score: MatchScore,
+
+    /// The sorted, de-duplicated character positions in the item's string
+    /// representation that the winning match fragments covered, so callers
+    /// can highlight them.
+    pub positions: Vec<usize>,
+}
+
+impl<T> Result<T> {
+    /// The composite score this item matched the query with.
+    pub fn score(&self) -> &MatchScore {
+        &self.score
+    }
+}
+
+impl<T> Deref for Result<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.item
+    }
+}
+
+/// Given a haystack of any `ToString` items, `find` returns a set of `Result`
+/// objects ordered best-first (closest matches lead). If the result set is
+/// larger than `max_results`, it is reduced to that size. Path-specific
+/// scoring (slash and camelCase boundaries) falls out of each item's string
+/// representation, so the same engine fuzzy-matches file paths, command
+/// names, buffer titles or symbol lists alike.
+///
+/// Letter case is handled per `options` (see `MatchOptions`); the default is
+/// smart case, staying insensitive until the query itself contains an
+/// uppercase character.
+///
+/// # Examples
+///
+/// ```rust
+/// use bloodhound::matching::{find, MatchOptions};
+///
+/// let commands = vec!["save".to_string(), "split".to_string()];
+/// let matches = find("sp", &commands, 1, &MatchOptions::default());
+///
+/// assert_eq!(*matches[0], "split".to_string());
+/// ```
+pub fn find<T: ToString + Clone>(needle: &str, haystack: &[T], max_results: usize,
+    options: &MatchOptions) -> Vec<Result<T>> {
+    let mut heap: BinaryHeap<Ranked<T>> = BinaryHeap::new();
+
+    // Build the query's character bag once to prefilter items that can't
+    // possibly contain every query character before the fragment walk.
+    let query_bag = CharBag::from_str(&needle.to_lowercase());
+
+    for item in haystack.iter() {
+        // Score the item via a transient entry built from its string form;
+        // this is where the path-aware boundary scoring lives.
+        let entry = entry::new(item.to_string());
+        if !entry.char_bag().is_superset_of(&query_bag) {
+            continue;
+        }
+
+        let (score, positions) = entry.similarity(needle, options);
+        heap.push(Ranked(Result{
+            item: item.clone(),
+            score: score,
+            positions: positions
+        }));
+
+        if heap.len() > max_results {
+            heap.pop();
+        }
+    }
+
+    ranked_results(heap)
 }
 
-/// Given a set of path entries, `find` returns a set of `Result` objects
-/// ordered by increasing score values (first values are closest matches).
-/// If the result set is larger than `max_results`, the set is reduced to
-/// that size.
+/// Like `find`, but checks `cancel` every `CANCELLATION_INTERVAL` entries while
+/// scoring and returns early (with whatever results it has gathered so far,
+/// ranked best-first) once another thread sets the flag. Interactive "type to
+/// filter" UIs pass a fresh `Arc<AtomicBool>` per keystroke and trip the
+/// previous search's flag as soon as the query changes, so no work is wasted
+/// on a query the user has already moved past.
+///
+/// This is the path-specialized counterpart to the generic `find`: it takes
+/// pre-built `Entry` values (so their char index and bag are computed once, not
+/// per query) and yields `Result<PathBuf>`.
 ///
 /// # Examples
 ///
 /// ```rust
+/// use std::sync::Arc;
+/// use std::sync::atomic::AtomicBool;
 /// use bloodhound::matching::entry;
-/// use std::path::PathBuf;
-/// use bloodhound::matching::find;
+/// use bloodhound::matching::find_cancellable;
+///
+/// use bloodhound::matching::MatchOptions;
+///
+/// let entries = vec![entry::new("lib.rs".to_string())];
+/// let cancel = Arc::new(AtomicBool::new(false));
+/// let matches = find_cancellable("lib", &entries, 1, &MatchOptions::default(), Some(&cancel));
+///
+/// assert_eq!(matches[0].to_str().unwrap(), "lib.rs");
+/// ```
+pub fn find_cancellable(
+    needle: &str,
+    haystack: &[Entry],
+    max_results: usize,
+    options: &MatchOptions,
+    cancel: Option<&Arc<AtomicBool>>,
+) -> Vec<Result<PathBuf>> {
+    // Keep only the best `max_results` matches in a bounded min-heap rather
+    // than scoring, fully sorting, then truncating the whole haystack. The
+    // heap's top is always the *worst* retained match (see `Ranked`'s
+    // ordering), so once it overflows we pop that worst element. This costs
+    // O(n log k) instead of O(n log n), a large win when k is small (5-10)
+    // and n is huge.
+    let mut heap: BinaryHeap<Ranked<PathBuf>> = BinaryHeap::new();
+
+    // Build the query's character bag once; entries whose bag doesn't cover
+    // it are missing a required character and can be skipped before the
+    // expensive fragment walk. The bag is keyed off the lowercased query to
+    // stay a conservative superset across smart-case matching.
+    let query_bag = CharBag::from_str(&needle.to_lowercase());
+
+    // Calculate a score for each of the haystack entries.
+    for (scored, entry) in haystack.iter().enumerate() {
+        // Abandon the search as soon as a newer query cancels this one.
+        if scored % CANCELLATION_INTERVAL == 0 {
+            if let Some(cancel) = cancel {
+                if cancel.load(AtomicOrdering::Relaxed) {
+                    return ranked_results(heap);
+                }
+            }
+        }
+
+        if !entry.char_bag().is_superset_of(&query_bag) {
+            continue;
+        }
+
+        let (score, positions) = entry.similarity(needle, options);
+
+        // The char-bag prefilter only proves the query's characters are
+        // present *somewhere*; it can't catch a case or accent mismatch. Drop
+        // anything the scorer deemed a non-match so callers never see entries
+        // that don't actually match the query.
+        if !score.is_match() {
+            continue;
+        }
+
+        heap.push(Ranked(Result{
+            item: entry.path.clone(),
+            score: score,
+            positions: positions
+        }));
+
+        // Once we're over the limit, evict the worst match at the top.
+        if heap.len() > max_results {
+            heap.pop();
+        }
+    }
+
+    ranked_results(heap)
+}
+
+/// Wraps a `Result` so a `BinaryHeap` orders the *worst* match at the top:
+/// the heap is a max-heap, and `MatchScore` sorts best-first, so comparing the
+/// scores directly puts the largest (worst) score at the root, ready to be
+/// popped once the bounded heap overflows.
+struct Ranked<T>(Result<T>);
+
+impl<T> PartialEq for Ranked<T> {
+    fn eq(&self, other: &Ranked<T>) -> bool {
+        self.0.score == other.0.score
+    }
+}
+
+impl<T> Eq for Ranked<T> {}
+
+impl<T> PartialOrd for Ranked<T> {
+    fn partial_cmp(&self, other: &Ranked<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Ranked<T> {
+    fn cmp(&self, other: &Ranked<T>) -> Ordering {
+        self.0.score.cmp(&other.0.score)
+    }
+}
+
+/// Drains the bounded heap into a best-first `Vec`. `into_sorted_vec` yields
+/// ascending order, and since `MatchScore` sorts best-first that's already the
+/// "closest match leads" contract callers rely on.
+fn ranked_results<T>(heap: BinaryHeap<Ranked<T>>) -> Vec<Result<T>> {
+    heap.into_sorted_vec().into_iter().map(|ranked| ranked.0).collect()
+}
+
+/// Like `find`, but tolerant of small typos. The query is compiled once into
+/// a bounded Levenshtein automaton whose edit-distance budget grows with the
+/// query length (0 for very short queries, up to 2 for long ones), and every
+/// entry's path is streamed through it. Entries that can't be matched within
+/// the budget are dropped entirely; the realized edit distance is folded into
+/// the score so fewer edits rank higher. Distance-zero matches fall through to
+/// the exact fragment scorer, preserving the precise ranking for clean input.
+///
+/// # Examples
+///
+/// ```rust
+/// use bloodhound::matching::entry;
+/// use bloodhound::matching::find_fuzzy;
+/// use bloodhound::matching::MatchOptions;
 ///
 /// let entries = vec![
-///     entry::new("bloodhound.rs".to_string()),
-///     entry::new("lib.rs".to_string())
+///     entry::new("cargo.toml".to_string()),
+///     entry::new("readme.md".to_string())
 /// ];
-/// let matches = find("lib", &entries, 1);
+/// let matches = find_fuzzy("cagro", &entries, 1, &MatchOptions::default());
 ///
-/// assert_eq!(matches[0].path.to_str().unwrap(), "lib.rs");
+/// assert_eq!(matches[0].to_str().unwrap(), "cargo.toml");
 /// ```
-pub fn find(needle: &str, haystack: &Vec<Entry>, max_results: usize) -> Vec<Result> {
+pub fn find_fuzzy(needle: &str, haystack: &[Entry], max_results: usize,
+    options: &MatchOptions) -> Vec<Result<PathBuf>> {
     let mut results = Vec::new();
 
-    // Calculate a score for each of the haystack entries.
+    // Fuzzy matching folds case, so compile the automaton against the
+    // lowercased query once, up front.
+    let lowercase_needle = needle.to_lowercase();
+    let automaton = Automaton::new(
+        &lowercase_needle,
+        levenshtein::max_distance(lowercase_needle.chars().count()),
+    );
+
     for entry in haystack.iter() {
-        results.push(Result{
-            path: entry.path.clone(),
-            score: entry.similarity(needle)
-        });
-    }
-
-    // Sort the results in ascending order (higher values are worse).
-    results.sort_by(|a, b| {
-        if a.score > b.score {
-            Ordering::Less
-        } else if a.score < b.score {
-            Ordering::Greater
-        } else {
-            Ordering::Equal
+        // Defer to the entry's fuzzy scorer; entries beyond the automaton's
+        // edit-distance bound return `None` and leave the result set.
+        if let Some((score, positions)) = entry.similarity_fuzzy(needle, &automaton, options) {
+            results.push(Result{
+                item: entry.path.clone(),
+                score: score,
+                positions: positions
+            });
         }
-    });
+    }
+
+    // The composite score orders best-first, so a plain ascending sort puts
+    // the closest matches at the front.
+    results.sort_by(|a, b| a.score.cmp(&b.score));
 
     // Make sure we don't exceed the specified result limit.
     results.truncate(max_results);
@@ -63,6 +323,8 @@ pub fn find(needle: &str, haystack: &Vec<Entry>, max_results: usize) -> Vec<Resu
 mod tests {
     use super::entry;
     use super::find;
+    use super::find_fuzzy;
+    use super::MatchOptions;
     use std::path::PathBuf;
 
     #[test]
@@ -72,13 +334,61 @@ mod tests {
             entry::new("lib/hounds.rs".to_string()),
             entry::new("Houndfile".to_string())
         ];
+        // All three paths contain "hound"; "Houndfile" leads on its earlier
+        // offset, and the shorter "src/hound.rs" edges out "lib/hounds.rs" on
+        // the path-length tiebreaker despite their otherwise identical scores.
         let expected_results = vec![PathBuf::from("Houndfile"), PathBuf::from("src/hound.rs")];
-        let results = find("Hound", &haystack, 2);
+        let results = find("hound", &haystack, 2, &MatchOptions::default());
         for i in 0..2 {
             assert_eq!(results[i].path, expected_results[i]);
         }
     }
 
+    #[test]
+    fn find_fuzzy_tolerates_a_single_typo() {
+        let haystack = vec![
+            entry::new("src/hound.rs".to_string()),
+            entry::new("lib/cargo.toml".to_string())
+        ];
+
+        // "hund" is one deletion away from "hound".
+        let results = find_fuzzy("hund", &haystack, 5, &MatchOptions::default());
+        assert_eq!(*results[0], PathBuf::from("src/hound.rs"));
+    }
+
+    #[test]
+    fn find_fuzzy_scores_the_whole_haystack_through_one_automaton() {
+        let haystack = vec![
+            entry::new("src/matching.rs".to_string()),
+            entry::new("src/index.rs".to_string())
+        ];
+
+        // "matchng" is a single deletion from "matching"; the query compiles
+        // one automaton that every entry streams through, and the realized
+        // edit distance folds into the score so the near-match leads.
+        let results = find_fuzzy("matchng", &haystack, 5, &MatchOptions::default());
+        assert_eq!(*results[0], PathBuf::from("src/matching.rs"));
+        assert_eq!(results[0].score().typos, 1);
+    }
+
+    #[test]
+    fn find_fuzzy_excludes_entries_beyond_the_distance_bound() {
+        let haystack = vec![entry::new("src/hound.rs".to_string())];
+
+        // "xyzzy" can't be matched against the entry within the budget.
+        let results = find_fuzzy("xyzzy", &haystack, 5, &MatchOptions::default());
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn find_matches_arbitrary_to_string_items() {
+        // The engine isn't tied to paths; here it fuzzy-matches command names.
+        let commands = vec!["save".to_string(), "split".to_string(), "quit".to_string()];
+        let results = find("sp", &commands, 1, &MatchOptions::default());
+
+        assert_eq!(*results[0], "split".to_string());
+    }
+
     #[test]
     fn find_returns_a_correctly_limited_set_of_results() {
         let haystack = vec![
@@ -86,7 +396,20 @@ mod tests {
             entry::new("lib/hounds.rs".to_string()),
             entry::new("Houndfile".to_string())
         ];
-        let results = find("Hound", &haystack, 2);
+        let results = find("hound", &haystack, 2, &MatchOptions::default());
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn find_forces_case_sensitivity_regardless_of_query_case() {
+        let haystack = vec!["Cargo.toml".to_string()];
+
+        // Smart case would fold an all-lowercase query, but the case-sensitive
+        // option pins matching to the exact case, so "cargo" no longer matches.
+        let insensitive = find("cargo", &haystack, 1, &MatchOptions::default());
+        let sensitive = find("cargo", &haystack, 1, &MatchOptions::case_sensitive());
+
+        assert!(insensitive[0].score().is_match());
+        assert!(!sensitive[0].score().is_match());
+    }
 }
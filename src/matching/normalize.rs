@@ -0,0 +1,99 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+/// Folds a string into the canonical form used for case-insensitive matching.
+///
+/// The same transformation is applied to both indexed path strings and
+/// incoming queries so that, for example, a query of `cafe` matches an indexed
+/// `Café`. The steps are:
+///
+/// 1. Unicode canonical decomposition (NFD), splitting accented characters
+///    into a base character plus combining marks.
+/// 2. Optional stripping of those combining marks, gated behind
+///    `strip_accents` so callers that want strict, accent-preserving matching
+///    can opt out.
+/// 3. Unicode lowercasing via `char::to_lowercase`, which handles far more of
+///    the alphabet than a naive ASCII lowercasing.
+///
+/// The result is only ever used for scoring; the original, byte-exact path is
+/// kept elsewhere for filesystem access. Because this transformation can change
+/// length, positions found against the normalized form are translated back onto
+/// the original path via `normalize_with_map` before being surfaced to callers.
+pub fn normalize(input: &str, strip_accents: bool) -> String {
+    input
+        .nfd()
+        .filter(|&c| !strip_accents || !is_combining_mark(c))
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Like `normalize`, but also returns, for each character in the normalized
+/// output, the char index of the original-string character it was derived
+/// from. Normalization is not length-preserving — decomposition and
+/// lowercasing can add or drop characters — so highlighting uses this map to
+/// translate match positions found against the normalized form back onto the
+/// byte-exact path the caller actually displays.
+pub fn normalize_with_map(input: &str, strip_accents: bool) -> (String, Vec<usize>) {
+    let mut normalized = String::new();
+    let mut map = Vec::new();
+
+    for (original_index, original_char) in input.chars().enumerate() {
+        for decomposed in original_char.nfd() {
+            if strip_accents && is_combining_mark(decomposed) {
+                continue;
+            }
+
+            for folded in decomposed.to_lowercase() {
+                normalized.push(folded);
+                map.push(original_index);
+            }
+        }
+    }
+
+    (normalized, map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+
+    #[test]
+    fn normalize_strips_accents_when_enabled() {
+        assert_eq!(normalize("Café", true), "cafe");
+    }
+
+    #[test]
+    fn normalize_preserves_accents_when_disabled() {
+        // The combining mark survives, but case is still folded.
+        assert_eq!(normalize("Café", false), normalize("café", false));
+        assert!(normalize("Café", false) != "cafe");
+    }
+
+    #[test]
+    fn normalize_folds_case() {
+        assert_eq!(normalize("README", true), "readme");
+    }
+
+    #[test]
+    fn normalize_with_map_tracks_original_indices_across_expansion() {
+        use super::normalize_with_map;
+
+        // With accents preserved, the precomposed "é" decomposes into a base
+        // character plus a combining mark — two normalized characters that both
+        // map back to the single original character at index 0.
+        let (normalized, map) = normalize_with_map("éx", false);
+        assert_eq!(normalized.chars().count(), 3);
+        assert_eq!(map, vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn normalize_with_map_tracks_original_indices_across_stripped_accents() {
+        use super::normalize_with_map;
+
+        // The combining mark on "é" is dropped, so the surviving base
+        // character still maps back to the original accented character.
+        let (normalized, map) = normalize_with_map("aé", true);
+        assert_eq!(normalized, "ae");
+        assert_eq!(map, vec![0, 1]);
+    }
+}
@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+
+/// A multi-criteria match score, compared lexicographically so that higher
+/// priority criteria dominate lower ones. This lets ranking express "fewer
+/// typos beats more matched characters beats tighter proximity" without
+/// collapsing everything into a single lossy `f32`.
+///
+/// Fields compare in declaration order, but not all ascending: see the `Ord`
+/// implementation for the per-field direction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MatchScore {
+    /// Edit distance consumed by the match (fewer is better).
+    pub typos: usize,
+
+    /// Number of needle characters covered by the match (more is better).
+    pub matched_chars: usize,
+
+    /// Sum of the gaps between consecutive matched needle characters in the
+    /// path (smaller is better — tightly-clustered matches win).
+    pub proximity: usize,
+
+    /// How many needle characters landed on a word boundary — immediately
+    /// after a `/`, `_`, `-`, `.`, or a camelCase transition (more is better).
+    pub boundary_bonus: usize,
+
+    /// How many matched characters already share the query's exact case (more
+    /// is better). A small tiebreaker so that, among otherwise-identical
+    /// subsequence matches, the case-correct one ranks higher.
+    pub case_bonus: usize,
+
+    /// Total length of the matched path (shorter is better). Breaks ties
+    /// between paths that match the query identically — a query buried in a
+    /// short path is a more specific hit than the same query in a long one.
+    pub total_length: usize,
+
+    /// Index of the first matched character (earlier is better), used only as
+    /// a final tiebreaker.
+    pub offset: usize,
+}
+
+impl MatchScore {
+    /// A score representing "no match at all", ordered worse than any real
+    /// match so non-matching entries sink to the bottom of the result set.
+    pub fn none() -> MatchScore {
+        MatchScore{
+            typos: usize::max_value(),
+            matched_chars: 0,
+            proximity: usize::max_value(),
+            boundary_bonus: 0,
+            case_bonus: 0,
+            total_length: usize::max_value(),
+            offset: usize::max_value(),
+        }
+    }
+
+    /// True when the score represents an actual match.
+    pub fn is_match(&self) -> bool {
+        self.matched_chars > 0
+    }
+}
+
+impl Ord for MatchScore {
+    /// Orders best-first: fewer typos, then more matched characters, then
+    /// tighter proximity, then more boundary hits, then a stronger case bonus,
+    /// then a shorter path, then an earlier offset.
+    fn cmp(&self, other: &MatchScore) -> Ordering {
+        self.typos.cmp(&other.typos)
+            .then_with(|| other.matched_chars.cmp(&self.matched_chars))
+            .then_with(|| self.proximity.cmp(&other.proximity))
+            .then_with(|| other.boundary_bonus.cmp(&self.boundary_bonus))
+            .then_with(|| other.case_bonus.cmp(&self.case_bonus))
+            .then_with(|| self.total_length.cmp(&other.total_length))
+            .then_with(|| self.offset.cmp(&other.offset))
+    }
+}
+
+impl PartialOrd for MatchScore {
+    fn partial_cmp(&self, other: &MatchScore) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchScore;
+
+    fn score(typos: usize, matched_chars: usize, proximity: usize) -> MatchScore {
+        MatchScore{ typos: typos, matched_chars: matched_chars, proximity: proximity,
+                    boundary_bonus: 0, case_bonus: 0, total_length: 0, offset: 0 }
+    }
+
+    #[test]
+    fn fewer_typos_wins_over_more_matched_chars() {
+        assert!(score(0, 2, 0) < score(1, 10, 0));
+    }
+
+    #[test]
+    fn more_matched_chars_wins_when_typos_tie() {
+        assert!(score(0, 5, 0) < score(0, 4, 0));
+    }
+
+    #[test]
+    fn tighter_proximity_wins_when_higher_criteria_tie() {
+        assert!(score(0, 4, 1) < score(0, 4, 5));
+    }
+
+    #[test]
+    fn none_sorts_after_any_real_match() {
+        assert!(score(2, 1, 100) < MatchScore::none());
+    }
+
+    #[test]
+    fn exact_case_bonus_breaks_otherwise_identical_ties() {
+        let case_correct = MatchScore{ case_bonus: 5, ..score(0, 5, 0) };
+        let case_folded = MatchScore{ case_bonus: 4, ..score(0, 5, 0) };
+        assert!(case_correct < case_folded);
+    }
+
+    #[test]
+    fn shorter_path_breaks_ties_below_the_case_bonus() {
+        let shorter = MatchScore{ total_length: 12, ..score(0, 5, 0) };
+        let longer = MatchScore{ total_length: 13, ..score(0, 5, 0) };
+        assert!(shorter < longer);
+    }
+}